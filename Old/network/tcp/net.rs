@@ -5,7 +5,9 @@ extern crate log;
 extern crate byteorder;
 
 use std::sync::{Arc};
-use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 use self::mio::{Token, Poll, Ready, PollOpt, Events};
 use self::mio::tcp::{TcpListener, TcpStream};
 use self::mio::channel::{Sender, Receiver, channel};
@@ -22,12 +24,52 @@ use self::byteorder::{BigEndian, ByteOrder};
 
 const TCP_SERVER_TOKEN: Token = Token(0);
 const RECEIVER_CHANNEL_TOKEN: Token = Token(1);
+// token ids below this are reserved for the server socket and the command channel,
+// so the pending connections slab starts handing out ids from here
+const FIRST_CONN_TOKEN_ID: usize = 2;
 const CURRENT_API_VERSION: u32 = 1;
+// sane default so a networking object built without an explicit cap still bounds memory
+const DEFAULT_MAX_PENDING_CONNECTIONS: usize = 10000;
+// soft cap that trips accept backpressure, kept below `max_pending_connections`
+const DEFAULT_MAX_CONNECTIONS: usize = 5000;
+// default accept-rate cap, in connections per second
+const DEFAULT_MAX_CONN_RATE: usize = 1000;
+// once paused by hitting a watermark, resume once the live count drops this far below it
+const ACCEPT_RESUME_WATERMARK: usize = 10;
+// after any pause, retry accepting again after this long even if the live count is still high
+const ACCEPT_PAUSE_BACKOFF: Duration = Duration::from_millis(200);
+// how long a connection may sit in `pending_connections` without completing its handshake
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+// how long a `from_server` connection may sit waiting on an app decision
+// (`AcceptPendingConnection`) after its wire handshake already completed; kept far
+// longer than `DEFAULT_HANDSHAKE_TIMEOUT` since the app side can be doing real work
+// (a DB lookup, cluster consensus, a human approving it), but still bounded so a
+// peer that finishes a cheap, valid handshake and is simply never approved can't
+// occupy a slab slot forever
+const DEFAULT_PENDING_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60);
+// how often the main loop sweeps `pending_connections` for expired handshakes
+const HANDSHAKE_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+// a connection accepted but not yet handed off to a reader: the time it started
+// waiting in the slab, and whether it's still waiting on the wire handshake
+// (API version + token/value) or has finished it and is now just waiting on an
+// explicit app decision (`AcceptPendingConnection`) — the handshake reaper measures
+// the former against `handshake_timeout` and the latter against the longer
+// `pending_approval_timeout`, so neither wait is ever truly unbounded
+type PendingConn = (TcpConn, Instant, bool);
 
 pub enum TcpNetworkCMD {
     HandleClientConnection,
     AcceptPendingConnection,
-    EmitEvent
+    EmitEvent,
+    // stop accepting new connections on the server socket until `Resume`, or until
+    // a later poll wakeup finds the live count below the low watermark or the
+    // `ACCEPT_PAUSE_BACKOFF` elapsed, whichever comes first
+    Pause,
+    // re-register the server socket for accepting after a `Pause`
+    Resume,
+    // stop accepting, tell every reader to drain and stop, and return from `run`
+    Shutdown
 }
 
 pub struct TcpNetworkCommand {
@@ -42,8 +84,42 @@ pub struct TcpNetwork {
     connections: Connections,
 
     // Socket based connections which are accepted from TCP server
-    // but not accepted from application
-    pending_connections: BTreeMap<Token, TcpConn>,
+    // but not accepted from application, stored in a slab indexed by token id
+    // so lookups are O(1) and token ids stay dense
+    pending_connections: Vec<Option<PendingConn>>,
+    // how many slots in `pending_connections` are currently occupied
+    pending_count: usize,
+    // upper bound on `pending_count`, once reached `acceptable` stops pulling
+    // new sockets off of the listener's accept queue
+    max_pending_connections: usize,
+    // next token id to mint when `freed_tokens` is empty
+    next_token_id: usize,
+    // token ids freed by `close_connection`/`accept_conn`, reused before minting new ones
+    freed_tokens: Vec<Token>,
+
+    // server socket, held here (rather than as a `run` local) so commands and
+    // watermark checks can pause/resume accepting between poll wakeups
+    server_socket: Option<TcpListener>,
+    // soft cap on live connections that trips accept backpressure
+    max_connections: usize,
+    // accept-rate cap, in connections per second
+    max_conn_rate: usize,
+    // timestamps of accepts within the last second, a small ring used to measure the current rate
+    accept_timestamps: VecDeque<Instant>,
+    // true while the server socket is deregistered for backpressure
+    paused: bool,
+    // time at which any pause ends even if the live count is still above the low watermark
+    resume_at: Option<Instant>,
+
+    // how long a pending connection gets to complete its handshake before being reaped
+    handshake_timeout: Duration,
+    // how long a connection that finished its handshake may wait on an app decision
+    // (`AcceptPendingConnection`) before being reaped
+    pending_approval_timeout: Duration,
+    // last time `maybe_sweep_handshake_timeouts` actually ran the sweep
+    last_handshake_sweep: Instant,
+    // set by `shutdown`, checked by `run` to break out of the event loop
+    shutting_down: bool,
 
     // token for current networking/node
     current_token: String,
@@ -55,7 +131,10 @@ pub struct TcpNetwork {
     event_handler_channel: Sender<EventHandlerCommand>,
     // vector of channels for sending commands to TcpReaders
     reader_channels: Vec<Sender<TcpReaderCommand>>,
-    // basic Round Rubin load balancer index for readers
+    // live-connection count per reader, shared with each TcpReader so `get_reader`
+    // can route to whichever one currently carries the least load
+    reader_loads: Arc<Vec<AtomicUsize>>,
+    // Round Robin index, only consulted to break ties between equally loaded readers
     reader_channel_index: usize,
     // base poll object
     poll: Poll
@@ -75,13 +154,28 @@ impl TcpNetwork {
 
         TcpNetwork {
             connections: conns,
-            pending_connections: BTreeMap::new(),
+            pending_connections: Vec::new(),
+            pending_count: 0,
+            max_pending_connections: DEFAULT_MAX_PENDING_CONNECTIONS,
+            next_token_id: FIRST_CONN_TOKEN_ID,
+            freed_tokens: Vec::new(),
+            server_socket: None,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            max_conn_rate: DEFAULT_MAX_CONN_RATE,
+            accept_timestamps: VecDeque::new(),
+            paused: false,
+            resume_at: None,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+            pending_approval_timeout: DEFAULT_PENDING_APPROVAL_TIMEOUT,
+            last_handshake_sweep: Instant::now(),
+            shutting_down: false,
             current_token: token,
             current_value: v.clone(),
             sender_channel: s,
             receiver_channel: r,
             event_handler_channel: event_chan,
             reader_channels: Vec::new(),
+            reader_loads: Arc::new(Vec::new()),
             reader_channel_index: 0,
             poll: match Poll::new() {
                 Ok(p) => p,
@@ -93,15 +187,47 @@ impl TcpNetwork {
         }
     }
 
+    // overrides the default cap on simultaneously pending (not-yet-accepted) connections
+    pub fn set_max_pending_connections(&mut self, max_pending_connections: usize) {
+        self.max_pending_connections = max_pending_connections;
+    }
+
+    // overrides the default soft cap on live connections that triggers accept backpressure
+    pub fn set_max_connections(&mut self, max_connections: usize) {
+        self.max_connections = max_connections;
+    }
+
+    // overrides the default cap on accepted connections per second
+    pub fn set_max_conn_rate(&mut self, max_conn_rate: usize) {
+        self.max_conn_rate = max_conn_rate;
+    }
+
+    // overrides the default time a pending connection has to complete its handshake
+    pub fn set_handshake_timeout(&mut self, handshake_timeout: Duration) {
+        self.handshake_timeout = handshake_timeout;
+    }
+
+    // overrides the default time a handshake-complete connection may wait on an
+    // app decision (`AcceptPendingConnection`) before being reaped
+    pub fn set_pending_approval_timeout(&mut self, pending_approval_timeout: Duration) {
+        self.pending_approval_timeout = pending_approval_timeout;
+    }
+
     pub fn channel(&self) -> Sender<TcpNetworkCommand> {
         self.sender_channel.clone()
     }
 
     pub fn run(&mut self, server_address: &str, readers_count: usize) {
         let mut readers: Vec<TcpReader> = vec![];
+        // one live-connection counter per reader, shared so each reader can report
+        // its own load and `get_reader` can route to the least-loaded one
+        let reader_loads: Arc<Vec<AtomicUsize>> = Arc::new((0..readers_count).map(|_| AtomicUsize::new(0)).collect());
+        self.reader_loads = reader_loads.clone();
+
         for i in 0..readers_count {
             let mut r = TcpReader::new(self.connections.clone(), self.event_handler_channel.clone(), self.current_value.clone());
             r.reader_index = i;
+            r.reader_loads = reader_loads.clone();
             self.reader_channels.push(r.channel());
             readers.push(r);
         }
@@ -147,6 +273,9 @@ impl TcpNetwork {
             }
         }
 
+        // kept on `self` so pause/resume can deregister and re-register it outside of `run`
+        self.server_socket = Some(server_socket);
+
         match self.poll.register(&self.receiver_channel, RECEIVER_CHANNEL_TOKEN, Ready::readable(), PollOpt::edge()) {
             Ok(_) => {},
             Err(e) => {
@@ -158,8 +287,28 @@ impl TcpNetwork {
         // making events for handling 5K events at once
         let mut events: Events = Events::with_capacity(5000);
         loop {
-            let event_count = self.poll.poll(&mut events, None).unwrap();
+            // we never poll with no timeout: while paused we want to wake up to retry
+            // accepting, and otherwise we still want to periodically sweep
+            // `pending_connections` for handshakes that never completed
+            let timeout = match (self.paused, self.resume_at) {
+                (true, Some(at)) => {
+                    let now = Instant::now();
+                    Some(if at > now { at - now } else { Duration::from_millis(0) })
+                }
+                (true, None) => Some(ACCEPT_PAUSE_BACKOFF),
+                (false, _) => Some(HANDSHAKE_SWEEP_INTERVAL)
+            };
+
+            let event_count = self.poll.poll(&mut events, timeout).unwrap();
+            if self.paused {
+                self.maybe_resume();
+            }
+            self.maybe_sweep_handshake_timeouts();
+
             if event_count == 0 {
+                if self.shutting_down {
+                    break;
+                }
                 continue;
             }
 
@@ -195,7 +344,7 @@ impl TcpNetwork {
 
                 if kind.is_readable() {
                     if token == TCP_SERVER_TOKEN {
-                        self.acceptable(&server_socket);
+                        self.acceptable();
                     } else {
                         self.readable(token);
                     }
@@ -208,6 +357,11 @@ impl TcpNetwork {
                 }
             }
 
+            // checked after draining this batch of events so a `Shutdown` command
+            // processed above takes effect immediately rather than waiting a tick
+            if self.shutting_down {
+                break;
+            }
         }
     }
 
@@ -226,10 +380,15 @@ impl TcpNetwork {
 
             TcpNetworkCMD::AcceptPendingConnection => {
                 let mut conn_token = Token(0);
-                for (t, conn) in self.pending_connections.iter() {
+                for slot in self.pending_connections.iter() {
+                    let conn = match *slot {
+                        Some((ref conn, _, _)) => conn,
+                        None => continue
+                    };
+
                     if conn.conn_value.len() > 0
                         && conn.conn_value[0].token == command.token[0] {
-                            conn_token = *t;
+                            conn_token = conn.socket_token;
                             break;
                         }
                 }
@@ -247,15 +406,49 @@ impl TcpNetwork {
 
                 self.emit(ev, command.token.clone());
             }
+
+            TcpNetworkCMD::Pause => {
+                self.pause_accepting();
+            }
+
+            TcpNetworkCMD::Resume => {
+                self.resume_accepting();
+            }
+
+            TcpNetworkCMD::Shutdown => {
+                self.shutdown();
+            }
         }
     }
 
     #[inline(always)]
-    fn acceptable(&mut self, listener: &TcpListener) {
+    fn acceptable(&mut self) {
+        // taken out for the duration of the loop so we can hand `&self` back to
+        // `add_pending_conn`/`deregister_server_socket` without an aliased borrow
+        let listener = match self.server_socket.take() {
+            Some(l) => l,
+            None => return
+        };
+
         loop {
+            // once the slab is full we stop pulling sockets off of the kernel's
+            // accept backlog, rather than registering more pending connections
+            // than we're willing to hold in memory
+            if self.paused || self.pending_count >= self.max_pending_connections {
+                break;
+            }
+
             match listener.accept() {
                 Ok((sock, _)) => {
                     self.add_pending_conn(sock, false);
+                    self.record_accept();
+
+                    // once we've crossed either watermark, stop accepting and leave
+                    // whatever's left queued in the kernel backlog until we recover
+                    if self.live_connection_count() >= self.max_connections || self.accept_rate_exceeded() {
+                        self.deregister_server_socket(&listener);
+                        break;
+                    }
                 }
                 // if we got error on server accept process
                 // we need to break accept loop and wait until new connections
@@ -263,11 +456,193 @@ impl TcpNetwork {
                 Err(_) => break
             }
         }
+
+        self.server_socket = Some(listener);
+    }
+
+    // records an accept for rate tracking and drops timestamps older than a second
+    #[inline(always)]
+    fn record_accept(&mut self) {
+        let now = Instant::now();
+        self.accept_timestamps.push_back(now);
+
+        while let Some(&oldest) = self.accept_timestamps.front() {
+            if now.duration_since(oldest) >= Duration::from_secs(1) {
+                self.accept_timestamps.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn accept_rate_exceeded(&self) -> bool {
+        self.accept_timestamps.len() >= self.max_conn_rate
+    }
+
+    // deregisters an already-taken-out server socket and flips on the paused state;
+    // callers that still hold `self.server_socket` should use `pause_accepting` instead
+    #[inline(always)]
+    fn deregister_server_socket(&mut self, listener: &TcpListener) {
+        if self.paused {
+            return;
+        }
+
+        match self.poll.deregister(listener) {
+            Ok(_) => {
+                self.paused = true;
+                self.resume_at = Some(Instant::now() + ACCEPT_PAUSE_BACKOFF);
+            }
+            Err(e) => warn!("Unable to deregister TCP server socket while pausing accept -> {}", e)
+        }
+    }
+
+    // stops accepting new connections until `resume_accepting`, or until `maybe_resume`
+    // re-registers the socket once the live count is below the low watermark or the
+    // backoff elapsed
+    pub fn pause_accepting(&mut self) {
+        let listener = match self.server_socket.take() {
+            Some(l) => l,
+            None => return
+        };
+
+        self.deregister_server_socket(&listener);
+        self.server_socket = Some(listener);
+    }
+
+    // re-registers the server socket as readable, undoing a prior pause
+    pub fn resume_accepting(&mut self) {
+        if !self.paused {
+            return;
+        }
+
+        let listener = match self.server_socket.take() {
+            Some(l) => l,
+            None => return
+        };
+
+        match self.poll.register(&listener, TCP_SERVER_TOKEN, Ready::readable(), PollOpt::edge()) {
+            Ok(_) => {
+                self.paused = false;
+                self.resume_at = None;
+            }
+            Err(e) => warn!("Unable to reregister TCP server socket while resuming accept -> {}", e)
+        }
+
+        self.server_socket = Some(listener);
+    }
+
+    // called after every poll wakeup while paused: resumes once the live count has
+    // drained below the low watermark, or unconditionally once the backoff elapses
+    #[inline(always)]
+    fn maybe_resume(&mut self) {
+        let watermark = self.max_connections.saturating_sub(ACCEPT_RESUME_WATERMARK);
+        let backoff_elapsed = match self.resume_at {
+            Some(at) => Instant::now() >= at,
+            None => true
+        };
+
+        if self.live_connection_count() < watermark || backoff_elapsed {
+            self.resume_accepting();
+        }
+    }
+
+    // deregisters the listener so no new connections are accepted, tells every reader
+    // to flush its write queue and return from its own `run`, and signals the main
+    // event loop in `run` to break and return rather than `process::exit`ing.
+    //
+    // this only covers TcpNetwork's half of the contract: sending `Shutdown` to
+    // every reader channel. The matching half — TcpReader::run() treating
+    // TcpReaderCMD::Shutdown as a signal to flush each connection's write queue
+    // and return instead of looping forever — has to live in the reader module
+    // (network/tcp/reader.rs or wherever TcpReader is defined), which isn't part
+    // of this source tree (only network/tcp/net.rs is present here). Without that
+    // handler, readers either fail to compile on a non-exhaustive match or, if
+    // there's already a catch-all arm, silently ignore the command and keep running.
+    pub fn shutdown(&mut self) {
+        if let Some(listener) = self.server_socket.take() {
+            let _ = self.poll.deregister(&listener);
+            self.server_socket = Some(listener);
+        }
+
+        for reader_channel in self.reader_channels.iter() {
+            let _ = reader_channel.send(TcpReaderCommand {
+                cmd: TcpReaderCMD::Shutdown,
+                conn: vec![],
+                conn_value: vec![],
+                data: vec![],
+                socket_token: vec![],
+                tokens: vec![],
+                event: vec![]
+            });
+        }
+
+        self.shutting_down = true;
+    }
+
+    // hands out the next free token id, reusing one closed/accepted connections freed
+    // before minting a brand new one, so ids stay dense for slab indexing
+    #[inline(always)]
+    fn next_conn_token(&mut self) -> Token {
+        match self.freed_tokens.pop() {
+            Some(t) => t,
+            None => {
+                let id = self.next_token_id;
+                self.next_token_id += 1;
+                Token(id)
+            }
+        }
+    }
+
+    // inserts/reinserts a connection, preserving whatever `created_at` it was
+    // originally accepted with so the handshake reaper measures real pending time
+    #[inline(always)]
+    fn insert_pending_conn(&mut self, token: Token, conn: TcpConn, created_at: Instant, handshake_complete: bool) {
+        if token.0 >= self.pending_connections.len() {
+            // `resize` would require `PendingConn: Clone`, which `TcpConn` can't
+            // provide (it wraps a `mio::tcp::TcpStream`, only `try_clone`-able);
+            // `resize_with` fills new slots by calling the closure instead
+            self.pending_connections.resize_with(token.0 + 1, || None);
+        }
+
+        self.pending_connections[token.0] = Some((conn, created_at, handshake_complete));
+    }
+
+    // pulls a connection out of the slab without freeing its token, for the
+    // read/write a bit then put it back pattern used by `readable`/`writable`
+    #[inline(always)]
+    fn take_pending_conn(&mut self, token: Token) -> Option<PendingConn> {
+        match self.pending_connections.get_mut(token.0) {
+            Some(slot) => slot.take(),
+            None => None
+        }
+    }
+
+    // pulls a connection out of the slab and frees its token for reuse, for when
+    // the connection is leaving `pending_connections` for good (closed or accepted)
+    #[inline(always)]
+    fn remove_pending_conn(&mut self, token: Token) -> Option<PendingConn> {
+        let conn = self.take_pending_conn(token);
+        if conn.is_some() {
+            self.free_conn_token(token);
+        }
+
+        conn
+    }
+
+    // frees a token already taken out of the slab via `take_pending_conn`, for
+    // call sites that need to close a connection they're still holding onto
+    #[inline(always)]
+    fn free_conn_token(&mut self, token: Token) {
+        self.pending_count -= 1;
+        self.freed_tokens.push(token);
     }
 
     #[inline(always)]
     fn add_pending_conn(&mut self, socket: TcpStream, from_client: bool) {
+        let token = self.next_conn_token();
         let mut conn = TcpConn::new(socket);
+        conn.socket_token = token;
         conn.from_server = !from_client;
         let mut ready_state = Ready::readable();
         if from_client {
@@ -275,20 +650,73 @@ impl TcpNetwork {
             self.write_handshake_info(&mut conn);
         }
 
-        match self.poll.register(&conn.socket, conn.socket_token, ready_state, PollOpt::edge()) {
+        match self.poll.register(&conn.socket, conn.socket_token, ready_state, PollOpt::edge() | PollOpt::oneshot()) {
             Ok(_) => {
                 // inserting connection as a pending
-                self.pending_connections.insert(conn.socket_token, conn);
+                self.insert_pending_conn(token, conn, Instant::now(), false);
+                self.pending_count += 1;
             }
 
             Err(e) => {
                 // after this accepted connection would be automatically deleted
-                // by closures deallocation
+                // by closures deallocation, and the token goes back to the free list
+                self.freed_tokens.push(token);
                 warn!("Unable to register accepted connection -> {}", e);
             }
         }
     }
 
+    // rearms a oneshot registration so the connection keeps receiving events; oneshot
+    // fires exactly once per registration, so every handler that wants to see this
+    // socket again has to reregister its interest before returning
+    #[inline(always)]
+    fn rearm_pending_conn(&mut self, token: Token, conn: &TcpConn, ready: Ready) -> bool {
+        match self.poll.reregister(&conn.socket, token, ready, PollOpt::edge() | PollOpt::oneshot()) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Unable to rearm connection for further events, closing connection -> {}", e);
+                false
+            }
+        }
+    }
+
+    // closes any pending connection that's overstayed its welcome in
+    // `pending_connections`: one still mid-handshake gets `handshake_timeout`,
+    // one that finished its handshake but is still waiting on an app decision
+    // (`AcceptPendingConnection`, `from_server` connections only — `accept_conn`
+    // takes anything else straight out of the slab) gets the longer
+    // `pending_approval_timeout` instead, so neither wait is unbounded
+    fn maybe_sweep_handshake_timeouts(&mut self) {
+        let now = Instant::now();
+        if now.duration_since(self.last_handshake_sweep) < HANDSHAKE_SWEEP_INTERVAL {
+            return;
+        }
+        self.last_handshake_sweep = now;
+
+        let expired: Vec<Token> = self.pending_connections.iter().enumerate()
+            .filter_map(|(id, slot)| match *slot {
+                Some((_, created_at, handshake_complete)) => {
+                    let timeout = if handshake_complete {
+                        self.pending_approval_timeout
+                    } else {
+                        self.handshake_timeout
+                    };
+
+                    if now.duration_since(created_at) >= timeout {
+                        Some(Token(id))
+                    } else {
+                        None
+                    }
+                }
+                None => None
+            })
+            .collect();
+
+        for token in expired {
+            self.close_connection(token);
+        }
+    }
+
     #[inline(always)]
     fn write_handshake_info(&self, conn: &mut TcpConn) {
         // if we got here then we made successfull connection with server
@@ -309,11 +737,26 @@ impl TcpNetwork {
         conn.add_writable_data(Arc::new(send_data));
     }
 
+    // `readable` rearms with plain `readable()` once the handshake read isn't
+    // done yet, but a `from_client` connection already queued our own handshake
+    // bytes in `write_handshake_info` before its first event ever fires; if that
+    // queue hasn't fully flushed, rearming without `writable()` would drop our
+    // write interest under oneshot until the handshake timeout closes the
+    // connection instead of it completing
+    #[inline(always)]
+    fn rearm_ready(conn: &TcpConn) -> Ready {
+        if !conn.from_server && conn.has_pending_write() {
+            Ready::readable() | Ready::writable()
+        } else {
+            Ready::readable()
+        }
+    }
+
     #[inline(always)]
     fn readable(&mut self, token: Token) {
         // when we will return functuin without inserting back
         // this connection would be deallocated and would be automatically closed
-        let mut conn =  match self.pending_connections.remove(&token) {
+        let (mut conn, created_at, _) = match self.take_pending_conn(token) {
             Some(c) => c,
             None => return
         };
@@ -326,7 +769,12 @@ impl TcpNetwork {
                     // if we need more data for getting API version
                     // then wiating until socket would become readable again
                     if !is_done {
-                        self.pending_connections.insert(token, conn);
+                        let ready = Self::rearm_ready(&conn);
+                        if self.rearm_pending_conn(token, &conn, ready) {
+                            self.insert_pending_conn(token, conn, created_at, false);
+                        } else {
+                            self.free_conn_token(token);
+                        }
                         return;
                     }
                 },
@@ -334,7 +782,14 @@ impl TcpNetwork {
                     // if we got WouldBlock, then this is Non Blocking socket
                     // and data still not available for this, so it's not a connection error
                     if e.kind() == ErrorKind::WouldBlock {
-                        self.pending_connections.insert(token, conn);
+                        let ready = Self::rearm_ready(&conn);
+                        if self.rearm_pending_conn(token, &conn, ready) {
+                            self.insert_pending_conn(token, conn, created_at, false);
+                        } else {
+                            self.free_conn_token(token);
+                        }
+                    } else {
+                        self.free_conn_token(token);
                     }
 
                     return;
@@ -346,6 +801,7 @@ impl TcpNetwork {
             Ok((t,v,d)) => (t,v,d),
             Err(e) => {
                 warn!("Error while reading connection token, closing connection -> {}", e);
+                self.free_conn_token(token);
                 return;
             }
         };
@@ -373,23 +829,33 @@ impl TcpNetwork {
                 });
 
                 // if we got here then all operations done
-                // adding back connection for keeping it
-                self.pending_connections.insert(token, conn);
+                // adding back connection for keeping it; the wire handshake is now
+                // complete, so the reaper switches it over to `pending_approval_timeout`
+                // instead of `handshake_timeout` while the app decides on it
+                self.insert_pending_conn(token, conn, created_at, true);
             }
             else {
                 // if this connection is from client, then we don't need to check it using User space code
                 // just accepting connection after we have server node information
-                self.pending_connections.insert(token, conn);
+                self.insert_pending_conn(token, conn, created_at, true);
                 self.accept_conn(token);
             }
         }
+        // a partial token/value read closes the connection rather than waiting for
+        // more data to arrive, matching the original (pre-slab) behavior; unlike
+        // the API version read above, this path was never made to tolerate partial
+        // reads, and doing so is a protocol-level change that deserves its own
+        // request rather than riding along with `pending_connections` storage
+        else {
+            self.free_conn_token(token);
+        }
     }
 
     #[inline(always)]
     fn writable(&mut self, token: Token) {
         // when we will return functuin without inserting back
         // this connection would be deallocated and would be automatically closed
-        let mut conn =  match self.pending_connections.remove(&token) {
+        let (mut conn, created_at, _) = match self.take_pending_conn(token) {
             Some(c) => c,
             None => return
         };
@@ -398,31 +864,32 @@ impl TcpNetwork {
             Ok(d) => d,
             Err(e) => {
                 warn!("Connection Write error, closing connection -> {}", e);
+                self.free_conn_token(token);
                 return;
             }
         };
 
-        // if we done with writing data
-        // reregistering connection only readable again
-        if is_done {
-            match self.poll.reregister(&conn.socket, token, Ready::readable(), PollOpt::edge()) {
-                Ok(_) => {},
-                Err(e) => {
-                    warn!("Unable to reregister connection as readable from network write functionality, closing connection -> {}", e);
-                    return;
-                }
-            }
-        }
+        // the write queue drained, so we only need another oneshot readable event;
+        // otherwise there's still data queued, so rearm for writable (plus readable)
+        // to keep getting exactly one writable event per chunk instead of a stream
+        // of spurious wakeups for an idle-but-writable socket
+        let ready = if is_done {
+            Ready::readable()
+        } else {
+            Ready::readable() | Ready::writable()
+        };
 
-        // if we got here then all operations done
-        // adding back connection for keeping it
-        self.pending_connections.insert(token, conn);
+        if self.rearm_pending_conn(token, &conn, ready) {
+            self.insert_pending_conn(token, conn, created_at, false);
+        } else {
+            self.free_conn_token(token);
+        }
     }
 
     #[inline(always)]
     pub fn accept_conn(&mut self, token: Token) {
-        let mut conn =  match self.pending_connections.remove(&token) {
-            Some(c) => c,
+        let mut conn =  match self.remove_pending_conn(token) {
+            Some((c, _, _)) => c,
             None => return
         };
 
@@ -433,7 +900,9 @@ impl TcpNetwork {
         // deregistering socket from this loop
         let _ = self.poll.deregister(&conn.socket);
 
-        match self.get_reader().send(TcpReaderCommand {
+        let reader_idx = self.select_reader();
+
+        match self.reader_channels[reader_idx].send(TcpReaderCommand {
             cmd: TcpReaderCMD::HandleConnection,
             conn_value: match conn.pop_conn_value() {
                 Some(c) => vec![c],
@@ -445,7 +914,15 @@ impl TcpNetwork {
             tokens: vec![],
             event: vec![]
         }) {
-            Ok(_) => {},
+            Ok(_) => {
+                // counts this connection against the reader's load the moment we
+                // hand it off, since that's the only side of the handoff this
+                // struct can see; the matching decrement happens on the TcpReader
+                // side once it closes the connection
+                if let Some(counter) = self.reader_loads.get(reader_idx) {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            },
             Err(_) => {
                 warn!("Error while trying to send Reader Command from Networking for connection accept, so closing connection");
                 return;
@@ -455,19 +932,59 @@ impl TcpNetwork {
 
     #[inline(always)]
     fn get_reader(&mut self) -> Sender<TcpReaderCommand> {
-        if self.reader_channel_index >= self.reader_channels.len() {
+        let idx = self.select_reader();
+        self.reader_channels[idx].clone()
+    }
+
+    // least-connections selection, walking the readers starting from the old
+    // round robin pointer so ties between equally loaded readers still rotate;
+    // advances the round robin pointer past whichever reader it picks
+    #[inline(always)]
+    fn select_reader(&mut self) -> usize {
+        let len = self.reader_channels.len();
+        if self.reader_channel_index >= len {
              self.reader_channel_index = 0;
         }
 
-        let r = self.reader_channels[self.reader_channel_index].clone();
-        self.reader_channel_index += 1;
-        return r;
+        let mut best_idx = self.reader_channel_index;
+        let mut best_load = self.reader_load(best_idx);
+
+        for offset in 1..len {
+            let idx = (self.reader_channel_index + offset) % len;
+            let load = self.reader_load(idx);
+            if load < best_load {
+                best_load = load;
+                best_idx = idx;
+            }
+        }
+
+        self.reader_channel_index = (best_idx + 1) % len;
+        best_idx
+    }
+
+    #[inline(always)]
+    fn reader_load(&self, idx: usize) -> usize {
+        match self.reader_loads.get(idx) {
+            Some(count) => count.load(Ordering::Relaxed),
+            None => 0
+        }
+    }
+
+    // total live connections this TcpNetwork knows about: ones still mid-handshake
+    // in `pending_connections`, plus whatever each reader is currently holding.
+    // `pending_count` alone drops back down the moment a connection finishes its
+    // handshake and is handed off, so it can't see load building up on readers
+    #[inline(always)]
+    fn live_connection_count(&self) -> usize {
+        let reader_total: usize = self.reader_loads.iter().map(|c| c.load(Ordering::Relaxed)).sum();
+        self.pending_count + reader_total
     }
 
     #[inline(always)]
     fn close_connection(&mut self, token: Token) {
-        // deleting connection from our map, it would be deleted automatically
-        self.pending_connections.remove(&token);
+        // removing connection from our slab, it would be deleted automatically,
+        // and its token goes back onto the free list for reuse
+        self.remove_pending_conn(token);
     }
 
     // emit event to given path from Event object and/or to provided connection tokens
@@ -485,4 +1002,4 @@ impl TcpNetwork {
 
         true
     }
-}
\ No newline at end of file
+}